@@ -1,5 +1,7 @@
 // lib.rs
 
+mod post_process;
+
 // Standard library imports
 use std::iter; // Provides utility methods for iterator operations
 
@@ -13,10 +15,261 @@ use winit::{
 
 use wgpu::util::DeviceExt;
 
+use post_process::{ PassDesc, PostProcess };
+
 // Import for WebAssembly (wasm32) target, if applicable
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
 
+// A single point of geometry: position, a per-vertex color, and a texture coordinate.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Vertex {
+    position: [f32; 3],
+    color: [f32; 3],
+    tex_coords: [f32; 2],
+}
+
+impl Vertex {
+    // Describes how the vertex buffer is laid out for the render pipeline
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress * 2,
+                    shader_location: 2,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+            ],
+        }
+    }
+}
+
+// A unit square in the XY plane, built from two triangles
+const VERTICES: &[Vertex] = &[
+    Vertex { position: [-0.5, -0.5, 0.0], color: [1.0, 0.0, 0.0], tex_coords: [0.0, 1.0] },
+    Vertex { position: [0.5, -0.5, 0.0], color: [0.0, 1.0, 0.0], tex_coords: [1.0, 1.0] },
+    Vertex { position: [0.5, 0.5, 0.0], color: [0.0, 0.0, 1.0], tex_coords: [1.0, 0.0] },
+    Vertex { position: [-0.5, 0.5, 0.0], color: [1.0, 1.0, 0.0], tex_coords: [0.0, 0.0] },
+];
+
+const INDICES: &[u16] = &[0, 1, 2, 2, 3, 0];
+
+// A simple look-at perspective camera
+struct Camera {
+    eye: glam::Vec3,
+    target: glam::Vec3,
+    up: glam::Vec3,
+    aspect: f32,
+    fovy: f32,
+    znear: f32,
+    zfar: f32,
+}
+
+impl Camera {
+    // Builds the combined projection * view matrix for the current camera state
+    fn build_view_projection_matrix(&self) -> glam::Mat4 {
+        let view = glam::Mat4::look_at_rh(self.eye, self.target, self.up);
+        let proj = glam::Mat4::perspective_rh(self.fovy, self.aspect, self.znear, self.zfar);
+        proj * view
+    }
+}
+
+// Creates the depth texture and its view, sized to match the surface
+fn create_depth_texture(
+    device: &wgpu::Device,
+    config: &wgpu::SurfaceConfiguration,
+    sample_count: u32,
+    label: &str
+) -> (wgpu::Texture, wgpu::TextureView) {
+    let size = wgpu::Extent3d {
+        width: config.width.max(1),
+        height: config.height.max(1),
+        depth_or_array_layers: 1,
+    };
+
+    let texture = device.create_texture(
+        &(wgpu::TextureDescriptor {
+            label: Some(label),
+            size,
+            mip_level_count: 1,
+            sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Depth32Float,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        })
+    );
+
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    (texture, view)
+}
+
+// Creates the multisampled color texture used as the render target before it is resolved
+// onto the swapchain image
+fn create_msaa_texture(
+    device: &wgpu::Device,
+    config: &wgpu::SurfaceConfiguration,
+    sample_count: u32
+) -> wgpu::TextureView {
+    let size = wgpu::Extent3d {
+        width: config.width.max(1),
+        height: config.height.max(1),
+        depth_or_array_layers: 1,
+    };
+
+    let texture = device.create_texture(
+        &(wgpu::TextureDescriptor {
+            label: Some("MSAA Framebuffer"),
+            size,
+            mip_level_count: 1,
+            sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format: config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        })
+    );
+
+    texture.create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+// Returns the requested sample count if the adapter supports it for this format, and
+// 1 (no MSAA) otherwise; no intermediate counts are tried
+fn choose_sample_count(adapter: &wgpu::Adapter, format: wgpu::TextureFormat, requested: u32) -> u32 {
+    let flags = adapter.get_texture_format_features(format).flags;
+    if flags.sample_count_supported(requested) {
+        requested
+    } else {
+        1
+    }
+}
+
+// A small built-in checkerboard used before the user loads a real texture
+fn placeholder_texture_bytes() -> image::RgbaImage {
+    image::RgbaImage::from_fn(2, 2, |x, y| {
+        if (x + y) % 2 == 0 {
+            image::Rgba([255, 255, 255, 255])
+        } else {
+            image::Rgba([0, 0, 0, 255])
+        }
+    })
+}
+
+// A second built-in test pattern, swapped in by `State::toggle_texture` (the T key)
+// to exercise `State::load_texture`'s PNG-decode path — there's no bundled asset
+// file for it to load from disk instead.
+fn stripes_texture_bytes() -> image::RgbaImage {
+    image::RgbaImage::from_fn(64, 64, |x, y| {
+        if (x + y) % 16 < 8 {
+            image::Rgba([220, 60, 60, 255])
+        } else {
+            image::Rgba([60, 60, 220, 255])
+        }
+    })
+}
+
+// Encodes a raw RGBA image to PNG bytes so it can be round-tripped through
+// `State::load_texture`, which expects encoded image bytes rather than raw pixels
+fn encode_png(image: &image::RgbaImage) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    image::DynamicImage::ImageRgba8(image.clone())
+        .write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageFormat::Png)
+        .expect("Failed to encode texture as PNG");
+    bytes
+}
+
+// A minimal built-in two-pass `.slangp`-style preset, toggled on with the P key:
+// downsample the scene to a quarter of its resolution and then nearest-neighbor
+// upscale it back, producing a pixelated look. The downsample pass is the whole
+// reason `PostProcess` has to size its intermediate textures per-pass instead of
+// reusing full-resolution buffers.
+const PIXELATE_PRESET: &str =
+    r#"
+[downsample]
+shader=@group(0) @binding(0) var src: texture_2d<f32>; @group(0) @binding(1) var src_sampler: sampler; struct PassUniforms { source_resolution: vec2<f32>, output_resolution: vec2<f32> }; @group(0) @binding(2) var<uniform> uniforms: PassUniforms; @fragment fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> { return textureSample(src, src_sampler, in.uv); }
+scale=0.25
+filter=linear
+wrap=clamp
+
+[upscale]
+shader=@group(0) @binding(0) var src: texture_2d<f32>; @group(0) @binding(1) var src_sampler: sampler; struct PassUniforms { source_resolution: vec2<f32>, output_resolution: vec2<f32> }; @group(0) @binding(2) var<uniform> uniforms: PassUniforms; @fragment fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> { return textureSample(src, src_sampler, in.uv); }
+scale=4.0
+filter=nearest
+wrap=clamp
+"#;
+
+// Uploads a decoded RGBA image to a sampled texture and returns its view and sampler
+fn create_texture_from_image(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    image: &image::RgbaImage,
+    label: &str
+) -> (wgpu::Texture, wgpu::TextureView, wgpu::Sampler) {
+    let dimensions = image.dimensions();
+    let size = wgpu::Extent3d {
+        width: dimensions.0,
+        height: dimensions.1,
+        depth_or_array_layers: 1,
+    };
+
+    let texture = device.create_texture(
+        &(wgpu::TextureDescriptor {
+            label: Some(label),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        })
+    );
+
+    queue.write_texture(
+        wgpu::ImageCopyTexture {
+            texture: &texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        image,
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(4 * dimensions.0),
+            rows_per_image: Some(dimensions.1),
+        },
+        size
+    );
+
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    let sampler = device.create_sampler(
+        &(wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Nearest,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        })
+    );
+
+    (texture, view, sampler)
+}
+
 // The main state struct which holds all resources needed for rendering
 struct State<'a> {
     surface: wgpu::Surface<'a>, // Surface that represents the part of the window where rendering occurs
@@ -26,9 +279,29 @@ struct State<'a> {
     size: winit::dpi::PhysicalSize<u32>, // Window size in physical pixels
     window: &'a Window, // Reference to the window instance for rendering
     render_pipeline: wgpu::RenderPipeline, // The pipeline object that contains rendering configurations
+    challenge_render_pipeline: wgpu::RenderPipeline, // Flat-color variant, toggled at draw time
+    use_color: bool, // Selects which of the two pipelines `render` draws with
     uniform_buffer: wgpu::Buffer,
     bind_group: wgpu::BindGroup,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    num_indices: u32,
     rotation_angle: f32,
+    rotation_speed: f32,
+    paused: bool,
+    last_frame: instant::Instant,
+    texture_bind_group_layout: wgpu::BindGroupLayout,
+    diffuse_texture: wgpu::Texture,
+    diffuse_texture_view: wgpu::TextureView,
+    diffuse_sampler: wgpu::Sampler,
+    texture_bind_group: wgpu::BindGroup,
+    camera: Camera,
+    depth_texture: wgpu::Texture,
+    depth_view: wgpu::TextureView,
+    sample_count: u32,
+    msaa_view: wgpu::TextureView,
+    post_process: Option<PostProcess>,
+    using_alt_texture: bool, // Which built-in texture `toggle_texture` last loaded
 }
 
 // Implementation of the State struct
@@ -104,6 +377,10 @@ impl<'a> State<'a> {
             view_formats: vec![],
         };
 
+        // Prefer 4x MSAA, but fall back to no multisampling if the adapter can't do it
+        // for this surface format
+        let sample_count = choose_sample_count(&adapter, surface_format, 4);
+
         // Load the WGSL shader code from an external file
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Shader"),
@@ -150,58 +427,160 @@ impl<'a> State<'a> {
             })
         );
 
-        // Set up the render pipeline layout with an empty layout as no resources are bound
+        // Bind group layout for the diffuse texture and its sampler (group 1)
+        let texture_bind_group_layout = device.create_bind_group_layout(
+            &(wgpu::BindGroupLayoutDescriptor {
+                label: Some("Texture Bind Group Layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            multisampled: false,
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+            })
+        );
+
+        // Upload the built-in placeholder image as the initial diffuse texture
+        let (diffuse_texture, diffuse_texture_view, diffuse_sampler) = create_texture_from_image(
+            &device,
+            &queue,
+            &placeholder_texture_bytes(),
+            "Diffuse Texture"
+        );
+
+        let texture_bind_group = device.create_bind_group(
+            &(wgpu::BindGroupDescriptor {
+                layout: &texture_bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&diffuse_texture_view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&diffuse_sampler),
+                    },
+                ],
+                label: Some("Texture Bind Group"),
+            })
+        );
+
+        // Set up the render pipeline layout, wiring both the uniform and texture bind groups
         let render_pipeline_layout = device.create_pipeline_layout(
             &wgpu::PipelineLayoutDescriptor {
                 label: Some("Render Pipeline Layout"),
-                bind_group_layouts: &[&bind_group_layout], // Add bind group layout
+                bind_group_layouts: &[&bind_group_layout, &texture_bind_group_layout],
                 push_constant_ranges: &[],
             },
-        );        
-
-        // Create the render pipeline, specifying shaders, topology, and blend options
-        let render_pipeline = device.create_render_pipeline(
-            &(wgpu::RenderPipelineDescriptor {
-                label: Some("Render Pipeline"),
-                layout: Some(&render_pipeline_layout),
-                vertex: wgpu::VertexState {
-                    module: &shader,
-                    entry_point: "vs_main", // Vertex shader entry point
-                    buffers: &[], // No vertex buffer in this example
-                    compilation_options: wgpu::PipelineCompilationOptions::default(),
-                },
-                fragment: Some(wgpu::FragmentState {
-                    module: &shader,
-                    entry_point: "fs_main", // Fragment shader entry point
-                    targets: &[
-                        Some(wgpu::ColorTargetState {
-                            format: config.format,
-                            blend: Some(wgpu::BlendState::REPLACE), // Overwrites previous color values
-                            write_mask: wgpu::ColorWrites::ALL,
-                        }),
-                    ],
-                    compilation_options: wgpu::PipelineCompilationOptions::default(),
-                }),
-                primitive: wgpu::PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::TriangleList,
-                    strip_index_format: None,
-                    front_face: wgpu::FrontFace::Ccw,
-                    cull_mode: None, // Changed from Some(wgpu::Face::Back)
-                    polygon_mode: wgpu::PolygonMode::Fill,
-                    unclipped_depth: false,
-                    conservative: false,
-                },
-                depth_stencil: None, // No depth or stencil buffer used in this example
-                multisample: wgpu::MultisampleState {
-                    count: 1,
-                    mask: !0,
-                    alpha_to_coverage_enabled: false,
-                },
-                multiview: None,
-                cache: None, // Add this to disable caching
+        );
+
+        // Builds a render pipeline against the given fragment entry point, sharing everything
+        // else (vertex layout, bind group layouts, depth/MSAA state) between variants
+        let build_pipeline = |label: &str, fragment_entry_point: &'static str| {
+            device.create_render_pipeline(
+                &(wgpu::RenderPipelineDescriptor {
+                    label: Some(label),
+                    layout: Some(&render_pipeline_layout),
+                    vertex: wgpu::VertexState {
+                        module: &shader,
+                        entry_point: "vs_main", // Vertex shader entry point
+                        buffers: &[Vertex::desc()],
+                        compilation_options: wgpu::PipelineCompilationOptions::default(),
+                    },
+                    fragment: Some(wgpu::FragmentState {
+                        module: &shader,
+                        entry_point: fragment_entry_point,
+                        targets: &[
+                            Some(wgpu::ColorTargetState {
+                                format: config.format,
+                                blend: Some(wgpu::BlendState::REPLACE), // Overwrites previous color values
+                                write_mask: wgpu::ColorWrites::ALL,
+                            }),
+                        ],
+                        compilation_options: wgpu::PipelineCompilationOptions::default(),
+                    }),
+                    primitive: wgpu::PrimitiveState {
+                        topology: wgpu::PrimitiveTopology::TriangleList,
+                        strip_index_format: None,
+                        front_face: wgpu::FrontFace::Ccw,
+                        cull_mode: None, // Changed from Some(wgpu::Face::Back)
+                        polygon_mode: wgpu::PolygonMode::Fill,
+                        unclipped_depth: false,
+                        conservative: false,
+                    },
+                    depth_stencil: Some(wgpu::DepthStencilState {
+                        format: wgpu::TextureFormat::Depth32Float,
+                        depth_write_enabled: true,
+                        depth_compare: wgpu::CompareFunction::Less,
+                        stencil: wgpu::StencilState::default(),
+                        bias: wgpu::DepthBiasState::default(),
+                    }),
+                    multisample: wgpu::MultisampleState {
+                        count: sample_count,
+                        mask: !0,
+                        alpha_to_coverage_enabled: false,
+                    },
+                    multiview: None,
+                    cache: None, // Add this to disable caching
+                })
+            )
+        };
+
+        // The default pipeline outputs the interpolated vertex color modulated by the
+        // diffuse texture; the challenge pipeline outputs a flat shade instead
+        let render_pipeline = build_pipeline("Render Pipeline", "fs_main");
+        let challenge_render_pipeline = build_pipeline("Challenge Render Pipeline", "fs_solid");
+
+        // Upload the geometry to GPU-visible vertex/index buffers
+        let vertex_buffer = device.create_buffer_init(
+            &(wgpu::util::BufferInitDescriptor {
+                label: Some("Vertex Buffer"),
+                contents: bytemuck::cast_slice(VERTICES),
+                usage: wgpu::BufferUsages::VERTEX,
+            })
+        );
+
+        let index_buffer = device.create_buffer_init(
+            &(wgpu::util::BufferInitDescriptor {
+                label: Some("Index Buffer"),
+                contents: bytemuck::cast_slice(INDICES),
+                usage: wgpu::BufferUsages::INDEX,
             })
         );
 
+        let num_indices = INDICES.len() as u32;
+
+        // Set up a camera looking at the origin from slightly above and in front
+        let camera = Camera {
+            eye: glam::Vec3::new(0.0, 1.0, 2.0),
+            target: glam::Vec3::ZERO,
+            up: glam::Vec3::Y,
+            aspect: (config.width.max(1) as f32) / (config.height.max(1) as f32),
+            fovy: 45.0_f32.to_radians(),
+            znear: 0.1,
+            zfar: 100.0,
+        };
+
+        let (depth_texture, depth_view) = create_depth_texture(
+            &device,
+            &config,
+            sample_count,
+            "Depth Texture"
+        );
+        let msaa_view = create_msaa_texture(&device, &config, sample_count);
+
         // Configure the surface with device and configuration
         surface.configure(&device, &config);
 
@@ -213,12 +592,93 @@ impl<'a> State<'a> {
             size,
             window,
             render_pipeline,
+            challenge_render_pipeline,
+            use_color: true,
             uniform_buffer,
             bind_group,
+            vertex_buffer,
+            index_buffer,
+            num_indices,
             rotation_angle: 0.0,
+            rotation_speed: 0.5,
+            paused: false,
+            last_frame: instant::Instant::now(),
+            texture_bind_group_layout,
+            diffuse_texture,
+            diffuse_texture_view,
+            diffuse_sampler,
+            texture_bind_group,
+            camera,
+            depth_texture,
+            depth_view,
+            sample_count,
+            msaa_view,
+            post_process: None,
+            using_alt_texture: false,
+        }
+    }
+
+    // Installs a post-processing filter chain; pass an empty `Vec` to remove it.
+    // Parse a preset into `PassDesc`s with `PassDesc::parse_preset` first.
+    pub fn set_post_chain(&mut self, passes: Vec<PassDesc>) {
+        self.post_process = if passes.is_empty() {
+            None
+        } else {
+            Some(PostProcess::new(&self.device, self.config.format, self.config.width, self.config.height, passes))
+        };
+    }
+
+    // Toggles the built-in pixelate preset on and off
+    fn toggle_post_chain(&mut self) {
+        let active = self.post_process.as_ref().is_some_and(PostProcess::has_passes);
+        if active {
+            self.set_post_chain(Vec::new());
+        } else {
+            self.set_post_chain(PassDesc::parse_preset(PIXELATE_PRESET));
         }
     }
 
+    // Decodes image bytes (PNG, JPEG, ...) and replaces the diffuse texture with them
+    pub fn load_texture(&mut self, bytes: &[u8]) {
+        let image = image::load_from_memory(bytes).expect("Failed to decode texture bytes").to_rgba8();
+
+        let (diffuse_texture, diffuse_texture_view, diffuse_sampler) = create_texture_from_image(
+            &self.device,
+            &self.queue,
+            &image,
+            "Diffuse Texture"
+        );
+
+        self.texture_bind_group = self.device.create_bind_group(
+            &(wgpu::BindGroupDescriptor {
+                layout: &self.texture_bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&diffuse_texture_view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&diffuse_sampler),
+                    },
+                ],
+                label: Some("Texture Bind Group"),
+            })
+        );
+
+        self.diffuse_texture = diffuse_texture;
+        self.diffuse_texture_view = diffuse_texture_view;
+        self.diffuse_sampler = diffuse_sampler;
+    }
+
+    // Swaps between the built-in checkerboard and stripes textures, round-tripping
+    // through `load_texture`'s PNG-decode path since there's no asset file to load instead
+    fn toggle_texture(&mut self) {
+        self.using_alt_texture = !self.using_alt_texture;
+        let image = if self.using_alt_texture { stripes_texture_bytes() } else { placeholder_texture_bytes() };
+        self.load_texture(&encode_png(&image));
+    }
+
     // Accessor for the window reference
     fn window(&self) -> &Window {
         &self.window
@@ -231,41 +691,123 @@ impl<'a> State<'a> {
             self.config.width = new_size.width;
             self.config.height = new_size.height;
             self.surface.configure(&self.device, &self.config);
+            self.camera.aspect = (new_size.width as f32) / (new_size.height as f32);
+            let (depth_texture, depth_view) = create_depth_texture(
+                &self.device,
+                &self.config,
+                self.sample_count,
+                "Depth Texture"
+            );
+            self.depth_texture = depth_texture;
+            self.depth_view = depth_view;
+            self.msaa_view = create_msaa_texture(&self.device, &self.config, self.sample_count);
+            if let Some(post_process) = &mut self.post_process {
+                post_process.resize(&self.device, new_size.width, new_size.height);
+            }
         }
     }
 
-    // Handles input events, returning false as no input handling is done in this example
-    #[allow(unused_variables)]
+    // Handles keyboard input: Space pauses, Up/Down (or +/-) adjust speed, Left/Right flip
+    // direction, R resets the rotation back to zero, C toggles between the vertex-color
+    // and solid-color pipelines, T swaps the diffuse texture, and P toggles the built-in
+    // pixelate post-process chain
     fn input(&mut self, event: &WindowEvent) -> bool {
-        false
+        let WindowEvent::KeyboardInput {
+            event: KeyEvent { state: ElementState::Pressed, physical_key: PhysicalKey::Code(code), .. },
+            ..
+        } = event else {
+            return false;
+        };
+
+        match code {
+            KeyCode::Space => {
+                self.paused = !self.paused;
+                true
+            }
+            KeyCode::KeyR => {
+                self.rotation_angle = 0.0;
+                true
+            }
+            KeyCode::ArrowUp | KeyCode::Equal => {
+                self.rotation_speed += 0.1;
+                true
+            }
+            KeyCode::ArrowDown | KeyCode::Minus => {
+                self.rotation_speed -= 0.1;
+                true
+            }
+            KeyCode::ArrowLeft | KeyCode::ArrowRight => {
+                self.rotation_speed = -self.rotation_speed;
+                true
+            }
+            KeyCode::KeyC => {
+                self.use_color = !self.use_color;
+                true
+            }
+            KeyCode::KeyT => {
+                self.toggle_texture();
+                true
+            }
+            KeyCode::KeyP => {
+                self.toggle_post_chain();
+                true
+            }
+            _ => false,
+        }
     }
 
-    // Update function (empty in this example as no animations or transformations are applied)
+    // Advances the rotation (framerate-independent, unless paused) and uploads the
+    // combined model-view-projection matrix
     fn update(&mut self) {
-        self.rotation_angle += 0.001; // Rotate by a small angle each frame
-        let transform = glam::Mat4::from_rotation_z(self.rotation_angle);
-        self.queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&transform.to_cols_array()));
+        let now = instant::Instant::now();
+        let dt = now.duration_since(self.last_frame).as_secs_f32();
+        self.last_frame = now;
+
+        if !self.paused {
+            self.rotation_angle += self.rotation_speed * dt;
+        }
+
+        let model = glam::Mat4::from_rotation_z(self.rotation_angle);
+        let mvp = self.camera.build_view_projection_matrix() * model;
+        self.queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&mvp.to_cols_array()));
     }
 
     // Render function that performs the drawing operations
     fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
         let output = self.surface.get_current_texture()?; // Get the next texture for rendering
         let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default()); // Create a view for the texture
-    
+
+        // The 3D scene draws straight to the swapchain (or its MSAA target) unless a
+        // post-process chain is active, in which case it draws into the chain's
+        // off-screen scene texture instead
+        let post_process_active = self.post_process.as_ref().is_some_and(PostProcess::has_passes);
+        let scene_target = match &self.post_process {
+            Some(post_process) if post_process_active => post_process.scene_view(),
+            _ => &view,
+        };
+
+        // When multisampling, render into the MSAA texture and resolve it onto the
+        // scene target; otherwise draw straight into the scene target
+        let (color_view, resolve_target) = if self.sample_count > 1 {
+            (&self.msaa_view, Some(scene_target))
+        } else {
+            (scene_target, None)
+        };
+
         let mut encoder = self.device.create_command_encoder(
             &wgpu::CommandEncoderDescriptor {
                 label: Some("Render Encoder"),
             },
         );
-    
+
         // Start the render pass
         let mut render_pass = encoder.begin_render_pass(
             &wgpu::RenderPassDescriptor {
                 label: Some("Render Pass"),
                 color_attachments: &[
                     Some(wgpu::RenderPassColorAttachment {
-                        view: &view,
-                        resolve_target: None,
+                        view: color_view,
+                        resolve_target,
                         ops: wgpu::Operations {
                             load: wgpu::LoadOp::Clear(wgpu::Color {
                                 r: 0.1,
@@ -277,18 +819,35 @@ impl<'a> State<'a> {
                         },
                     }),
                 ],
-                depth_stencil_attachment: None,
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                }),
                 occlusion_query_set: None,
                 timestamp_writes: None,
             },
         );
     
-        render_pass.set_pipeline(&self.render_pipeline); // Set the render pipeline
+        // Select the vertex-color or flat-color pipeline; both share the layout and
+        // uniform/texture bind groups, so only the fragment behavior changes
+        let pipeline = if self.use_color { &self.render_pipeline } else { &self.challenge_render_pipeline };
+        render_pass.set_pipeline(pipeline); // Set the render pipeline
         render_pass.set_bind_group(0, &self.bind_group, &[]); // Bind the uniform buffer
-        render_pass.draw(0..6, 0..1); // Draw 6 vertices for two triangles
+        render_pass.set_bind_group(1, &self.texture_bind_group, &[]); // Bind the diffuse texture
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.draw_indexed(0..self.num_indices, 0, 0..1); // Draw the square from its index buffer
     
         drop(render_pass); // End the render pass
-    
+
+        if post_process_active {
+            self.post_process.as_ref().unwrap().execute(&mut encoder, &self.device, &self.queue, &view);
+        }
+
         self.queue.submit(iter::once(encoder.finish())); // Submit the command buffer for execution
         output.present(); // Present the rendered image to the window
     
@@ -0,0 +1,481 @@
+// post_process.rs
+//
+// An off-screen filter chain loosely modeled on RetroArch/librashader `.slangp`
+// presets: an ordered list of fragment passes, each sampling the previous pass's
+// output and writing into the next. Only the small subset of the preset format
+// this demo needs is supported (see `PassDesc::parse_preset`).
+
+use wgpu::util::DeviceExt;
+
+// Fullscreen-triangle vertex shader shared by every pass; the triangle covers the
+// viewport so no vertex/index buffer is needed.
+const FULLSCREEN_VERTEX_SHADER: &str =
+    "
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    out.uv = uv;
+    out.clip_position = vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+    return out;
+}
+";
+
+// Per-pass resolution uniforms, matching the std140-ish layout WGSL expects
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct PassUniforms {
+    source_resolution: [f32; 2],
+    output_resolution: [f32; 2],
+}
+
+// One entry of a parsed preset: a pass's shader plus how it samples and sizes itself
+#[derive(Clone, Debug)]
+pub struct PassDesc {
+    pub label: String,
+    pub fragment_shader: String,
+    pub scale: f32,
+    pub filter_mode: wgpu::FilterMode,
+    pub address_mode: wgpu::AddressMode,
+}
+
+impl PassDesc {
+    // Parses a simplified `.slangp`-style preset: one `[name]` section per pass,
+    // in order (the name becomes the pass label), with `shader`, `scale`, `filter` (`linear`/`nearest`) and `wrap`
+    // (`clamp`/`repeat`) keys. Unknown keys and blank/`#` comment lines are ignored.
+    pub fn parse_preset(preset: &str) -> Vec<PassDesc> {
+        let mut passes = Vec::new();
+        let mut label = String::new();
+        let mut fragment_shader = String::new();
+        let mut scale = 1.0_f32;
+        let mut filter_mode = wgpu::FilterMode::Linear;
+        let mut address_mode = wgpu::AddressMode::ClampToEdge;
+
+        let flush = |
+            passes: &mut Vec<PassDesc>,
+            label: &str,
+            fragment_shader: &str,
+            scale: f32,
+            filter_mode: wgpu::FilterMode,
+            address_mode: wgpu::AddressMode
+        | {
+            if !label.is_empty() {
+                passes.push(PassDesc {
+                    label: label.to_string(),
+                    fragment_shader: fragment_shader.to_string(),
+                    scale,
+                    filter_mode,
+                    address_mode,
+                });
+            }
+        };
+
+        for line in preset.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(section) = line.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+                flush(&mut passes, &label, &fragment_shader, scale, filter_mode, address_mode);
+                label = section.to_string();
+                fragment_shader.clear();
+                scale = 1.0;
+                filter_mode = wgpu::FilterMode::Linear;
+                address_mode = wgpu::AddressMode::ClampToEdge;
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+
+            match key {
+                "shader" => {
+                    fragment_shader = value.to_string();
+                }
+                "scale" => {
+                    scale = value.parse().unwrap_or(1.0);
+                }
+                "filter" => {
+                    filter_mode = if value.eq_ignore_ascii_case("nearest") {
+                        wgpu::FilterMode::Nearest
+                    } else {
+                        wgpu::FilterMode::Linear
+                    };
+                }
+                "wrap" => {
+                    address_mode = if value.eq_ignore_ascii_case("repeat") {
+                        wgpu::AddressMode::Repeat
+                    } else {
+                        wgpu::AddressMode::ClampToEdge
+                    };
+                }
+                _ => {}
+            }
+        }
+
+        flush(&mut passes, &label, &fragment_shader, scale, filter_mode, address_mode);
+        passes
+    }
+}
+
+// Bundles `run_pass`'s per-invocation arguments so the method doesn't grow an
+// unwieldy parameter list as the chain gains more per-pass context
+struct PassRunContext<'a> {
+    source_view: &'a wgpu::TextureView,
+    target_view: &'a wgpu::TextureView,
+    source_size: (u32, u32),
+    output_size: (u32, u32),
+}
+
+// A single compiled pass: its own pipeline (the fragment shader differs per pass),
+// sampler, and resolution uniform buffer
+struct CompiledPass {
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    uniform_buffer: wgpu::Buffer,
+    scale: f32,
+}
+
+// Owns the off-screen scene texture and the compiled filter chain that runs on it
+// before the result is blitted onto the swapchain
+pub struct PostProcess {
+    scene_texture: wgpu::Texture,
+    scene_view: wgpu::TextureView,
+    // One intermediate target per non-final pass, each sized from the chained
+    // `PassDesc::scale` of every pass before it (the final pass always resolves
+    // onto the swapchain view instead, at whatever size the caller hands in).
+    intermediates: Vec<(wgpu::Texture, wgpu::TextureView)>,
+    passes: Vec<CompiledPass>,
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+}
+
+impl PostProcess {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, width: u32, height: u32, passes: Vec<PassDesc>) -> Self {
+        let scene_texture = Self::create_target(device, format, width, height, "Post Process Scene Texture");
+        let scene_view = scene_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let compiled: Vec<CompiledPass> = passes
+            .into_iter()
+            .map(|desc| Self::compile_pass(device, format, &desc))
+            .collect();
+        let intermediates = Self::build_intermediates(device, format, width, height, &compiled);
+
+        Self {
+            scene_texture,
+            scene_view,
+            intermediates,
+            passes: compiled,
+            format,
+            width,
+            height,
+        }
+    }
+
+    // The render target the 3D scene itself should draw into before any filtering
+    pub fn scene_view(&self) -> &wgpu::TextureView {
+        &self.scene_view
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        self.scene_texture = Self::create_target(device, self.format, width, height, "Post Process Scene Texture");
+        self.scene_view = self.scene_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        self.intermediates = Self::build_intermediates(device, self.format, width, height, &self.passes);
+    }
+
+    // Recreates the chain's intermediate textures from scratch, sizing each one
+    // from the running resolution after every prior pass's `scale` — this is the
+    // critical invariant the chain depends on for any pass whose scale isn't 1.0.
+    // The final pass has no intermediate: it renders straight onto the swapchain.
+    fn build_intermediates(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+        passes: &[CompiledPass]
+    ) -> Vec<(wgpu::Texture, wgpu::TextureView)> {
+        let mut size = (width, height);
+        let mut intermediates = Vec::new();
+
+        let Some(last) = passes.len().checked_sub(1) else {
+            return intermediates;
+        };
+
+        for (index, pass) in passes.iter().enumerate() {
+            size = (
+                ((size.0 as f32) * pass.scale).round().max(1.0) as u32,
+                ((size.1 as f32) * pass.scale).round().max(1.0) as u32,
+            );
+            if index == last {
+                break;
+            }
+            intermediates.push(Self::create_target_pair(device, format, size.0, size.1, "Post Process Intermediate Texture"));
+        }
+
+        intermediates
+    }
+
+    // True once at least one pass has been configured; callers should skip the
+    // off-screen scene texture entirely (and render straight to the swapchain)
+    // when this is false.
+    pub fn has_passes(&self) -> bool {
+        !self.passes.is_empty()
+    }
+
+    // Runs the scene texture through every pass in the chain, resolving the final
+    // pass onto `swapchain_view`. Only call this when `has_passes` is true.
+    pub fn execute(&self, encoder: &mut wgpu::CommandEncoder, device: &wgpu::Device, queue: &wgpu::Queue, swapchain_view: &wgpu::TextureView) {
+        let mut source_view = &self.scene_view;
+        let mut source_size = (self.width, self.height);
+
+        for (index, pass) in self.passes.iter().enumerate() {
+            let is_last = index + 1 == self.passes.len();
+            // The final pass must report (and be clamped to) the swapchain's real
+            // extent, not a value re-derived from chained scales: `f32::round`ing
+            // through several passes doesn't generally land back on `self.width`/
+            // `self.height`, and `set_viewport`/`set_scissor_rect` against the
+            // actual target panic the instant the reported size overshoots it.
+            let output_size = if is_last {
+                (self.width, self.height)
+            } else {
+                (
+                    ((source_size.0 as f32) * pass.scale).round().max(1.0) as u32,
+                    ((source_size.1 as f32) * pass.scale).round().max(1.0) as u32,
+                )
+            };
+
+            let target_view = if is_last { swapchain_view } else { &self.intermediates[index].1 };
+
+            self.run_pass(encoder, device, queue, pass, &PassRunContext {
+                source_view,
+                target_view,
+                source_size,
+                output_size,
+            });
+
+            source_view = target_view;
+            source_size = output_size;
+        }
+    }
+
+    fn run_pass(&self, encoder: &mut wgpu::CommandEncoder, device: &wgpu::Device, queue: &wgpu::Queue, pass: &CompiledPass, ctx: &PassRunContext) {
+        queue.write_buffer(
+            &pass.uniform_buffer,
+            0,
+            bytemuck::cast_slice(
+                &[PassUniforms {
+                    source_resolution: [ctx.source_size.0 as f32, ctx.source_size.1 as f32],
+                    output_resolution: [ctx.output_size.0 as f32, ctx.output_size.1 as f32],
+                }]
+            )
+        );
+
+        let bind_group = Self::pass_bind_group(device, pass, ctx.source_view);
+
+        let mut render_pass = encoder.begin_render_pass(
+            &(wgpu::RenderPassDescriptor {
+                label: Some("Post Process Pass"),
+                color_attachments: &[
+                    Some(wgpu::RenderPassColorAttachment {
+                        view: ctx.target_view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                            store: wgpu::StoreOp::Store,
+                        },
+                    }),
+                ],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            })
+        );
+
+        render_pass.set_pipeline(&pass.pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        // Constrain the draw to the pass's output size; each target is allocated at
+        // that size, and the final pass's `output_size` is the swapchain extent.
+        render_pass.set_viewport(0.0, 0.0, ctx.output_size.0 as f32, ctx.output_size.1 as f32, 0.0, 1.0);
+        render_pass.set_scissor_rect(0, 0, ctx.output_size.0, ctx.output_size.1);
+        render_pass.draw(0..3, 0..1);
+    }
+
+    // Builds the per-draw bind group; the pass itself is stateless so this stays cheap
+    fn pass_bind_group(device: &wgpu::Device, pass: &CompiledPass, source_view: &wgpu::TextureView) -> wgpu::BindGroup {
+        // Bind groups are tiny and recreated per draw rather than cached, matching
+        // the rest of this demo's preference for straightforward code over
+        // micro-optimized resource pooling.
+        device.create_bind_group(
+            &(wgpu::BindGroupDescriptor {
+                label: Some("Post Process Bind Group"),
+                layout: &pass.bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(source_view) },
+                    wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(&pass.sampler) },
+                    wgpu::BindGroupEntry { binding: 2, resource: pass.uniform_buffer.as_entire_binding() },
+                ],
+            })
+        )
+    }
+
+    fn compile_pass(device: &wgpu::Device, format: wgpu::TextureFormat, desc: &PassDesc) -> CompiledPass {
+        let shader_source = format!("{}\n{}", FULLSCREEN_VERTEX_SHADER, desc.fragment_shader);
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(&desc.label),
+            source: wgpu::ShaderSource::Wgsl(shader_source.into()),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(
+            &(wgpu::BindGroupLayoutDescriptor {
+                label: Some("Post Process Pass Bind Group Layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            multisampled: false,
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
+            })
+        );
+
+        let sampler = device.create_sampler(
+            &(wgpu::SamplerDescriptor {
+                address_mode_u: desc.address_mode,
+                address_mode_v: desc.address_mode,
+                address_mode_w: desc.address_mode,
+                mag_filter: desc.filter_mode,
+                min_filter: desc.filter_mode,
+                mipmap_filter: desc.filter_mode,
+                ..Default::default()
+            })
+        );
+
+        let uniform_buffer = device.create_buffer_init(
+            &(wgpu::util::BufferInitDescriptor {
+                label: Some("Post Process Pass Uniforms"),
+                contents: bytemuck::cast_slice(
+                    &[PassUniforms { source_resolution: [0.0, 0.0], output_resolution: [0.0, 0.0] }]
+                ),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            })
+        );
+
+        let pipeline_layout = device.create_pipeline_layout(
+            &(wgpu::PipelineLayoutDescriptor {
+                label: Some("Post Process Pipeline Layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            })
+        );
+
+        let pipeline = device.create_render_pipeline(
+            &(wgpu::RenderPipelineDescriptor {
+                label: Some(&desc.label),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vs_main",
+                    buffers: &[],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: "fs_main",
+                    targets: &[
+                        Some(wgpu::ColorTargetState {
+                            format,
+                            blend: Some(wgpu::BlendState::REPLACE),
+                            write_mask: wgpu::ColorWrites::ALL,
+                        }),
+                    ],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: None,
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    unclipped_depth: false,
+                    conservative: false,
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState {
+                    count: 1,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                multiview: None,
+                cache: None,
+            })
+        );
+
+        CompiledPass {
+            pipeline,
+            bind_group_layout,
+            sampler,
+            uniform_buffer,
+            scale: desc.scale,
+        }
+    }
+
+    fn create_target(device: &wgpu::Device, format: wgpu::TextureFormat, width: u32, height: u32, label: &str) -> wgpu::Texture {
+        device.create_texture(
+            &(wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d { width: width.max(1), height: height.max(1), depth_or_array_layers: 1 },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            })
+        )
+    }
+
+    fn create_target_pair(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+        label: &str
+    ) -> (wgpu::Texture, wgpu::TextureView) {
+        let texture = Self::create_target(device, format, width, height, label);
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        (texture, view)
+    }
+}